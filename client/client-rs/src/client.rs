@@ -1,15 +1,13 @@
 use crate::{
   config::Config,
   input::{
-    adapter::common::{
-      InputButton,
-      InputEvent,
-      InputAdapter,
-    },
-    switch::{
-      SwitchPad,
-      EmulatedPad
-    }
+    InputButton,
+    InputEvent,
+    InputReader,
+  },
+  pad::{
+    SwitchPad,
+    EmulatedPad
   }
 };
 use std::{
@@ -41,7 +39,7 @@ pub struct Client {
   sock: UdpSocket,
   server_ip: String,
 
-  input_adapter: Box<dyn InputAdapter>,
+  input_adapter: Box<dyn InputReader>,
   input_map: HashMap<usize, usize>,
 
   pads: Vec<EmulatedPad>,
@@ -58,20 +56,31 @@ impl Client {
    */
   pub fn new(
     config: Config,
-    input_adapter: Box<dyn InputAdapter>
+    input_adapter: Box<dyn InputReader>
   ) -> Client {
+    // Unwrapping here might not be the best thing
+    let sock: UdpSocket = UdpSocket::bind("0.0.0.0:8000").unwrap();
+    // Reading feedback packets must never block the fixed-interval tick loop, so we just poll for
+    // one on every tick and move on if there isn't one waiting.
+    sock.set_nonblocking(true).unwrap();
+
+    let stick_configs = config.stick_configs_to_vec();
+    let mut pads: Vec<EmulatedPad> = c![EmulatedPad::new(), for _i in 0..4];
+    for (i, pad) in pads.iter_mut().enumerate() {
+      pad.set_stick_config(stick_configs[i]);
+    }
+
     return Client {
       config: config,
-      // Unwrapping here might not be the best thing
-      sock: UdpSocket::bind("0.0.0.0:8000").unwrap(),
+      sock: sock,
       server_ip: "".to_string(),
 
       input_adapter: input_adapter,
       input_map: HashMap::new(),
 
-      pads: c![EmulatedPad::new(), for _i in 0..4]
+      pads: pads
     }
-  } 
+  }
 
   // A method that sets the target server IP of this client.
   pub fn set_server_ip(&mut self, server_ip: &str) -> () {
@@ -86,21 +95,38 @@ impl Client {
   pub fn update_pads(&mut self) -> () {
     self.disconnect_inactive();
     self.parse_events();
+    for pad in &mut self.pads {
+      pad.resolve_modifiers();
+    }
+    self.poll_feedback();
+    self.drive_rumble();
   }
 
-  // A helper method that disconnects any gamepads that aren't connected.
+  /**
+   * A helper method that disconnects any gamepads that aren't connected, and frees their slot.
+   *
+   * This is the second tick of the disconnect handshake: a pad already flagged pending_disconnect
+   * (either by parse_events() seeing an explicit disconnect event, or by this same method on a
+   * prior tick) has already reported a con_type of 0 to the Switch by now, so it's safe to
+   * actually clear it and open the slot back up. A pad that's only just now found disconnected is
+   * flagged instead of cleared immediately, so it gets at least one tick at con_type 0 first.
+   */
   fn disconnect_inactive(&mut self) -> () {
     let mut i = 0;
     for pad in &mut self.pads {
       match pad.get_gamepad_id() {
         Some(gamepad_id) => {
           if !self.input_adapter.is_connected(gamepad_id) {
-            println!(
-              "Disconnected gamepad (id: {}) from slot {}.",
-              gamepad_id,
-              i + 1
-            );
-            pad.disconnect();
+            if pad.is_pending_disconnect() {
+              println!(
+                "Disconnected gamepad (id: {}) from slot {}.",
+                gamepad_id,
+                i + 1
+              );
+              pad.soft_disconnect();
+            } else {
+              pad.begin_disconnect();
+            }
           }
         },
         None => ()
@@ -115,6 +141,23 @@ impl Client {
    */
   fn parse_events(&mut self) -> () {
     for event in self.input_adapter.read() {
+      if let InputEvent::GamepadConnected(gamepad_id, name) = event {
+        println!(
+          "Gamepad connected (id: {}): {}. Press the right bumper to assign it to a slot.",
+          gamepad_id,
+          name
+        );
+        continue;
+      }
+      if let InputEvent::GamepadDisconnected(gamepad_id) = event {
+        if let Some(&i) = self.input_map.get(&gamepad_id) {
+          if *self.pads[i].get_gamepad_id() == Some(gamepad_id) {
+            self.pads[i].begin_disconnect();
+          }
+          self.input_map.remove(&gamepad_id);
+        }
+        continue;
+      }
       if let Some(i) = self.input_map.get(event.get_gamepad_id()) {
         if *self.pads[*i].get_gamepad_id() == Some(*event.get_gamepad_id()) {
           self.pads[*i].update(&event);
@@ -132,6 +175,61 @@ impl Client {
     }
   }
 
+  /**
+   * A helper method that reads every buffered rumble packet, if any, and stores the amplitudes on
+   * the matching slot's pad so drive_rumble() can forward them to the physical gamepad.
+   *
+   * The server sends one packet per active slot, shaped as a raw `[slot: u8, low_freq_amp: u16,
+   * high_freq_amp: u16]` (5 bytes, little-endian). buf is reset every iteration so a short or
+   * malformed datagram can't leave stale bytes from a prior packet to be parsed as if they were
+   * part of this one.
+   */
+  fn poll_feedback(&mut self) -> () {
+    loop {
+      let mut buf: [u8; 5] = [0; 5];
+      let len = match self.sock.recv_from(&mut buf) {
+        Ok((len, _)) => len,
+        Err(_) => break
+      };
+      if len != buf.len() {
+        println!("Ignoring a malformed feedback packet ({} bytes, expected {}).", len, buf.len());
+        continue;
+      }
+
+      let slot: usize = buf[0] as usize;
+      let low_freq_amp: u16 = u16::from_le_bytes([buf[1], buf[2]]);
+      let high_freq_amp: u16 = u16::from_le_bytes([buf[3], buf[4]]);
+      if let Some(pad) = self.pads.get_mut(slot) {
+        pad.set_rumble(Some((
+          (low_freq_amp as f32) / (u16::MAX as f32),
+          (high_freq_amp as f32) / (u16::MAX as f32)
+        )));
+      }
+    }
+  }
+
+  /**
+   * A helper method that forwards every connected pad's stored rumble amplitudes to its physical
+   * gamepad through the input adapter, every tick.
+   *
+   * This has to be unconditional, not just called on a change: SdlAdapter's rumble() forwards
+   * straight to SDL's own timed rumble API, which auto-stops after its duration, so a sustained
+   * rumble request has to keep being re-armed every tick to outlast that timer.
+   * GilrsInputReader's effect is kept alive and reused (see its rumble() doc comment), so calling
+   * it again with the same amplitudes is just a cheap no-op gain/play rather than a rebuild.
+   */
+  fn drive_rumble(&mut self) -> () {
+    for pad in &self.pads {
+      if let Some(gamepad_id) = pad.get_gamepad_id() {
+        let (low, high) = pad.get_rumble().unwrap_or((0.0, 0.0));
+        match self.input_adapter.rumble(gamepad_id, low, high, time::Duration::from_millis(100)) {
+          Err(e) => println!("{}", e),
+          Ok(_) => ()
+        }
+      }
+    }
+  }
+
   /**
    * A helper method that attempts to assign the given gamepad ID and switch pad
    * type to an open slot, while mapping said ID the corresponding index. Slots
@@ -144,13 +242,19 @@ impl Client {
     let mut i: usize = 0;
     for pad in &mut self.pads {
       if match pad.get_gamepad_id() {
-        Some(gamepad_id) => !self.input_adapter.is_connected(gamepad_id),
+        // A slot mid-disconnect-handshake stays closed until soft_disconnect() frees it.
+        Some(gamepad_id) =>
+          !self.input_adapter.is_connected(gamepad_id) && !pad.is_pending_disconnect(),
         None => true
       } {
         match self.config.pads_to_vec()[i] {
           Some(switch_pad) => {
             self.input_map.insert(*gamepad_id, i);
             pad.connect(gamepad_id, switch_pad);
+            if let Some(mode_profile) = self.config.mode_profiles_to_vec()[i].clone() {
+              pad.set_mode_profile(mode_profile);
+            }
+            pad.set_profile(self.config.button_profiles_to_vec()[i].clone());
             return Ok(
               format!(
                 "Gamepad (id: {}) connected to slot {}.",
@@ -179,7 +283,7 @@ impl Client {
    */
   pub fn update_server(&self) -> Result<(), String> {
     match self.sock.send_to(
-      &PackedData::new(&self.pads, 4).to_bytes(),
+      &PackedData::new(&self.pads, self.get_connected()).to_bytes(),
       format!("{}:8000", self.server_ip)
     ) {
       Err(e) => return Err(
@@ -189,6 +293,18 @@ impl Client {
     }
   }
 
+  // A helper method that returns the number of slots currently reporting a real pad type to the
+  // Switch, i.e. excluding unassigned slots and slots mid-disconnect-handshake.
+  fn get_connected(&self) -> i8 {
+    let mut connected: i8 = 0;
+    for pad in &self.pads {
+      if pad.get_packed_switch_pad().is_some() {
+        connected = connected + 1;
+      }
+    }
+    return connected;
+  }
+
   /**
    * A method disconnects all connected gamepads.
    *
@@ -205,7 +321,7 @@ impl Client {
     let start: time::Instant = time::Instant::now();
     while start.elapsed().as_millis() < 3000 {
       match self.sock.send_to(
-        &PackedData::new(&self.pads, 4).to_bytes(),
+        &PackedData::new(&self.pads, self.get_connected()).to_bytes(),
         format!("{}:8000", self.server_ip)
       ) {
         Err(e) => return Err(e.to_string()),
@@ -256,7 +372,7 @@ pub struct PackedData {
 }
 
 // Maps a switch pad (or lack thereof) to its integer counterpart.
-fn switch_pad_to_value(switch_pad: &Option<SwitchPad>) -> i8 {
+fn switch_pad_to_value(switch_pad: Option<SwitchPad>) -> i8 {
   return match switch_pad {
     Some(pad) => match pad {
       SwitchPad::ProController => 1,
@@ -274,28 +390,28 @@ impl PackedData {
       magic: 0x3276,
       connected: connected as u16,
 
-      con_type: switch_pad_to_value(pads[0].get_switch_pad()) as u16,
+      con_type: switch_pad_to_value(pads[0].get_packed_switch_pad()) as u16,
       keys: *pads[0].get_keyout() as u64,
       joy_l_x: pads[0].get_left().0,
       joy_l_y: pads[0].get_left().1,
       joy_r_x: pads[0].get_right().0,
       joy_r_y: pads[0].get_right().1,
 
-      con_type2: switch_pad_to_value(pads[1].get_switch_pad()) as u16,
+      con_type2: switch_pad_to_value(pads[1].get_packed_switch_pad()) as u16,
       keys2: *pads[1].get_keyout() as u64,
       joy_l_x2: pads[1].get_left().0,
       joy_l_y2: pads[1].get_left().1,
       joy_r_x2: pads[1].get_right().0,
       joy_r_y2: pads[1].get_right().1,
 
-      con_type3: switch_pad_to_value(pads[2].get_switch_pad()) as u16,
+      con_type3: switch_pad_to_value(pads[2].get_packed_switch_pad()) as u16,
       keys3: *pads[2].get_keyout() as u64,
       joy_l_x3: pads[2].get_left().0,
       joy_l_y3: pads[2].get_left().1,
       joy_r_x3: pads[2].get_right().0,
       joy_r_y3: pads[2].get_right().1,
 
-      con_type4: switch_pad_to_value(pads[3].get_switch_pad()) as u16,
+      con_type4: switch_pad_to_value(pads[3].get_packed_switch_pad()) as u16,
       keys4: *pads[3].get_keyout() as u64,
       joy_l_x4: pads[3].get_left().0,
       joy_l_y4: pads[3].get_left().1,
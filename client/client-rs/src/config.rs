@@ -1,6 +1,46 @@
-use crate::pad::SwitchPad;
+use crate::pad::{ButtonProfile, ModeProfile, SwitchPad};
 use serde::{Serialize, Deserialize};
 
+/**
+ * A struct representing a stick's radial deadzone and response curve.
+ *
+ * - inner_dz is the normalized magnitude below which the stick is treated as neutral, meant to
+ *   swallow the drift cheap sticks send at rest.
+ * - outer_dz is the magnitude at (or past) which the stick is treated as fully deflected.
+ * - curve is a sensitivity exponent applied to the rescaled magnitude; values above 1.0 give
+ *   finer control near the center at the cost of feeling "slower" overall.
+ */
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct StickConfig {
+  inner_dz: f32,
+  outer_dz: f32,
+  curve: f32
+}
+
+impl Default for StickConfig {
+  fn default() -> StickConfig {
+    return StickConfig {
+      inner_dz: 0.1,
+      outer_dz: 1.0,
+      curve: 1.0
+    }
+  }
+}
+
+impl StickConfig {
+  pub fn get_inner_dz(&self) -> f32 {
+    return self.inner_dz;
+  }
+
+  pub fn get_outer_dz(&self) -> f32 {
+    return self.outer_dz;
+  }
+
+  pub fn get_curve(&self) -> f32 {
+    return self.curve;
+  }
+}
+
 /**
  * A struct representing a configuration for a client.
  *
@@ -14,7 +54,29 @@ pub struct Config {
   switch_pad_1: Option<SwitchPad>,
   switch_pad_2: Option<SwitchPad>,
   switch_pad_3: Option<SwitchPad>,
-  switch_pad_4: Option<SwitchPad>
+  switch_pad_4: Option<SwitchPad>,
+
+  stick_config_1: StickConfig,
+  stick_config_2: StickConfig,
+  stick_config_3: StickConfig,
+  stick_config_4: StickConfig,
+
+  // Per-slot turbo/toggle modifiers, applied on top of the button mapping.
+  mode_profile_1: Option<ModeProfile>,
+  mode_profile_2: Option<ModeProfile>,
+  mode_profile_3: Option<ModeProfile>,
+  mode_profile_4: Option<ModeProfile>,
+
+  // Per-slot button remap overrides, consulted before the built-in default mapping.
+  button_profile_1: Option<ButtonProfile>,
+  button_profile_2: Option<ButtonProfile>,
+  button_profile_3: Option<ButtonProfile>,
+  button_profile_4: Option<ButtonProfile>,
+
+  // Path to an SDL gamecontrollerdb.txt-style mapping file to load on startup, so controllers
+  // SDL doesn't recognize out of the box (and their missing buttons like Guide) get mapped
+  // properly instead of falling into the "unmapped SDL button" error path.
+  controller_db_path: Option<String>
 }
 
 impl Default for Config {
@@ -24,12 +86,29 @@ impl Default for Config {
       switch_pad_1: Some(SwitchPad::ProController),
       switch_pad_2: Some(SwitchPad::ProController),
       switch_pad_3: Some(SwitchPad::ProController),
-      switch_pad_4: Some(SwitchPad::ProController)
+      switch_pad_4: Some(SwitchPad::ProController),
+
+      stick_config_1: StickConfig::default(),
+      stick_config_2: StickConfig::default(),
+      stick_config_3: StickConfig::default(),
+      stick_config_4: StickConfig::default(),
+
+      mode_profile_1: None,
+      mode_profile_2: None,
+      mode_profile_3: None,
+      mode_profile_4: None,
+
+      button_profile_1: None,
+      button_profile_2: None,
+      button_profile_3: None,
+      button_profile_4: None,
+
+      controller_db_path: None
     }
   }
 }
 
-impl Config { 
+impl Config {
   pub fn get_rawinput_fallback(&self) -> bool {
     return self.rawinput_fallback;
   }
@@ -42,4 +121,35 @@ impl Config {
       self.switch_pad_4
     );
   }
+
+  pub fn stick_configs_to_vec(&self) -> Vec<StickConfig> {
+    return vec!(
+      self.stick_config_1,
+      self.stick_config_2,
+      self.stick_config_3,
+      self.stick_config_4
+    );
+  }
+
+  pub fn mode_profiles_to_vec(&self) -> Vec<Option<ModeProfile>> {
+    return vec!(
+      self.mode_profile_1.clone(),
+      self.mode_profile_2.clone(),
+      self.mode_profile_3.clone(),
+      self.mode_profile_4.clone()
+    );
+  }
+
+  pub fn button_profiles_to_vec(&self) -> Vec<Option<ButtonProfile>> {
+    return vec!(
+      self.button_profile_1.clone(),
+      self.button_profile_2.clone(),
+      self.button_profile_3.clone(),
+      self.button_profile_4.clone()
+    );
+  }
+
+  pub fn get_controller_db_path(&self) -> &Option<String> {
+    return &self.controller_db_path;
+  }
 }
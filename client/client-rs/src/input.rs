@@ -1,11 +1,18 @@
+pub mod common;
+
 use gilrs::{
   Gilrs,
+  ev::filter::{axis_dpad_to_button, Filter},
+  ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder},
 };
 use multiinput::RawEvent;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::time::Duration;
 
 // An enum representing the buttons that are universally available on gamepads; I'd hope so, anyway.
-#[derive(PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum InputButton {
   North,
   South,
@@ -20,23 +27,169 @@ pub enum InputButton {
   DPadUp,
   DPadDown,
   DPadLeft,
-  DPadRight
+  DPadRight,
+  // The Switch's Home button. GilRs surfaces it as Button::Mode; on a controller SDL doesn't
+  // recognize out of the box, it's only reachable via an SDL controller-DB mapping.
+  Guide,
+  // The Switch's Capture button, surfaced by SDL as Button::Misc1. GilRs has no equivalent.
+  Capture,
+  // A button a reader's Mapping doesn't recognize, carrying the raw library code it was reported
+  // with (GilRs's Button, multiinput's index) cast to an integer. Lets flight sticks, extra
+  // paddles, etc. come through as an event instead of being dropped or panicking the reader, with
+  // enough information left for a user-defined Mapping to bind the code later.
+  Other(u32)
 }
 
 // An enum representing the axes that are universally available on gamepads.
-#[derive(Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum InputAxis {
   LeftX,
   LeftY,
   RightX,
-  RightY
+  RightY,
+  // An axis a reader's Mapping doesn't recognize; see InputButton::Other.
+  Other(u32)
+}
+
+/**
+ * A struct representing a raw library's button/axis codes mapped to InputButtons/InputAxes, plus
+ * a per-axis scale/sign factor applied on top of the mapped value.
+ *
+ * Readers like GilrsInputReader and MultiInputReader have no standard way to ask a controller
+ * what its buttons/axes mean, so they normally hardcode the mapping for whatever layout they were
+ * written against. A Mapping pulls that hardcoding out into data so it can be swapped for a
+ * different layout (e.g. a controller GilRs reports with its face buttons rotated) without
+ * touching the reader's code, and serializes with serde so a profile can be authored as TOML/JSON.
+ *
+ * Raw codes are keyed by their Debug-formatted string (e.g. "South", "LeftStickX") rather than
+ * the underlying library's enum type, since GilRs and multiinput don't share one.
+ *
+ * This deliberately stops short of the stick itself: deadzone/curve shaping lives solely in
+ * EmulatedPad (via StickConfig), which already combines a stick's separately-reported X/Y axis
+ * events before shaping them. Doing it again here would double-apply the same rescale and curve.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Mapping {
+  button_map: HashMap<String, InputButton>,
+  axis_map: HashMap<String, InputAxis>,
+  axis_value_map: HashMap<InputAxis, f32>
+}
+
+impl Mapping {
+  pub fn new() -> Mapping {
+    return Mapping {
+      button_map: HashMap::new(),
+      axis_map: HashMap::new(),
+      axis_value_map: HashMap::new()
+    }
+  }
+
+  pub fn bind_button(&mut self, code: &str, button: InputButton) -> () {
+    self.button_map.insert(code.to_string(), button);
+  }
+
+  pub fn bind_axis(&mut self, code: &str, axis: InputAxis) -> () {
+    self.axis_map.insert(code.to_string(), axis);
+  }
+
+  pub fn set_axis_value(&mut self, axis: InputAxis, factor: f32) -> () {
+    self.axis_value_map.insert(axis, factor);
+  }
+
+  pub fn get_button(&self, code: &str) -> Option<InputButton> {
+    return self.button_map.get(code).copied();
+  }
+
+  pub fn get_axis(&self, code: &str) -> Option<InputAxis> {
+    return self.axis_map.get(code).copied();
+  }
+
+  // The scale/sign factor to apply to a mapped axis's value; defaults to 1.0 (no change) for any
+  // axis without an override.
+  pub fn get_axis_value_factor(&self, axis: &InputAxis) -> f32 {
+    return *self.axis_value_map.get(axis).unwrap_or(&1.0);
+  }
+
+  // The built-in GilRs raw button/axis -> InputButton/InputAxis mapping, matching a standard
+  // Xbox-style layout.
+  pub fn default_gilrs() -> Mapping {
+    let mut mapping = Mapping::new();
+    mapping.bind_button("South", InputButton::South);
+    mapping.bind_button("East", InputButton::East);
+    mapping.bind_button("North", InputButton::North);
+    mapping.bind_button("West", InputButton::West);
+    mapping.bind_button("LeftTrigger", InputButton::LeftBumper);
+    mapping.bind_button("LeftTrigger2", InputButton::LeftTrigger);
+    mapping.bind_button("RightTrigger", InputButton::RightBumper);
+    mapping.bind_button("RightTrigger2", InputButton::RightTrigger);
+    mapping.bind_button("Start", InputButton::Start);
+    mapping.bind_button("Select", InputButton::Select);
+    mapping.bind_button("DPadUp", InputButton::DPadUp);
+    mapping.bind_button("DPadDown", InputButton::DPadDown);
+    mapping.bind_button("DPadLeft", InputButton::DPadLeft);
+    mapping.bind_button("DPadRight", InputButton::DPadRight);
+    mapping.bind_button("Mode", InputButton::Guide);
+    mapping.bind_axis("LeftStickX", InputAxis::LeftX);
+    mapping.bind_axis("LeftStickY", InputAxis::LeftY);
+    mapping.bind_axis("RightStickX", InputAxis::RightX);
+    mapping.bind_axis("RightStickY", InputAxis::RightY);
+    return mapping;
+  }
+
+  /**
+   * A preset for PlayStation controllers GilRs reports with the face buttons rotated relative to
+   * their physical position on some platforms: swaps South and East so the physically-bottom face
+   * button sends South (rather than East) as it would on an Xbox-style pad.
+   */
+  pub fn new_playstation() -> Mapping {
+    let mut mapping = Mapping::default_gilrs();
+    mapping.bind_button("South", InputButton::East);
+    mapping.bind_button("East", InputButton::South);
+    return mapping;
+  }
+
+  /**
+   * The built-in multiinput (RawInput) raw button/axis -> InputButton/InputAxis mapping, based on
+   * a DS4's layout; this is the only controller this reader has been tested against.
+   *
+   * For some reason, the DS4's right stick uses the Z and RZ axes (Z for horizontal, RZ for
+   * vertical), and their values happen to be inverted relative to the left stick's X/Y axes; the
+   * axis_value_map entries below correct that.
+   */
+  pub fn default_multiinput() -> Mapping {
+    let mut mapping = Mapping::new();
+    mapping.bind_button("0", InputButton::West);
+    mapping.bind_button("1", InputButton::South);
+    mapping.bind_button("2", InputButton::East);
+    mapping.bind_button("3", InputButton::North);
+    mapping.bind_button("4", InputButton::LeftBumper);
+    mapping.bind_button("5", InputButton::RightBumper);
+    mapping.bind_button("6", InputButton::LeftTrigger);
+    mapping.bind_button("7", InputButton::RightTrigger);
+    mapping.bind_button("8", InputButton::Select);
+    mapping.bind_button("9", InputButton::Start);
+    mapping.bind_axis("X", InputAxis::LeftX);
+    mapping.bind_axis("Y", InputAxis::LeftY);
+    mapping.bind_axis("Z", InputAxis::RightX);
+    mapping.bind_axis("RZ", InputAxis::RightY);
+    mapping.set_axis_value(InputAxis::RightX, -1.0);
+    mapping.set_axis_value(InputAxis::RightY, -1.0);
+    return mapping;
+  }
 }
 
 // An enum representing the different events possible on a gamepad.
 #[derive(Debug)]
 pub enum InputEvent {
   GamepadButton(usize, InputButton, f32),
-  GamepadAxis(usize, InputAxis, f32)
+  GamepadAxis(usize, InputAxis, f32),
+  // Pushed by readers that can tell us about a connect directly (GilRs, SDL, multiinput), carrying
+  // the gamepad's display name, so the client can log it immediately instead of waiting on an
+  // is_connected() poll to notice a new id.
+  GamepadConnected(usize, String),
+  // Pushed by readers that can tell us about a disconnect directly (GilRs, SDL, multiinput), so
+  // the client can start freeing a slot without waiting on the next is_connected() poll.
+  GamepadDisconnected(usize)
 }
 
 impl InputEvent {
@@ -44,7 +197,9 @@ impl InputEvent {
   pub fn get_gamepad_id(&self) -> &usize {
     return match self {
       Self::GamepadButton(gamepad_id, _, _) => gamepad_id,
-      Self::GamepadAxis(gamepad_id, _, _) => gamepad_id
+      Self::GamepadAxis(gamepad_id, _, _) => gamepad_id,
+      Self::GamepadConnected(gamepad_id, _) => gamepad_id,
+      Self::GamepadDisconnected(gamepad_id) => gamepad_id
     }
   }
 }
@@ -59,6 +214,23 @@ pub trait InputReader {
 
   // A method that checks the input library to verify if a gamepad of a given ID is connected.
   fn is_connected(&mut self, gamepad_id: &usize) -> bool;
+
+  /**
+   * A method that drives the given gamepad's rumble motors at the given low/high frequency
+   * amplitudes (0.0-1.0, normalized the same way as a button's value) for the given duration.
+   *
+   * This is the reverse leg of the protocol: the Switch reports rumble state to us, and the input
+   * library underneath this reader is what actually has to make the physical controller shake.
+   * Callers may call this every tick with the same amplitudes to keep a rumble going, passing
+   * (0.0, 0.0) to stop. SdlAdapter passes duration straight through to SDL's own timed rumble API;
+   * GilrsInputReader instead maps low/high onto a persistent GilRs dual-motor force-feedback
+   * effect per gamepad (reused via set_gain() until it's stopped), since GilRs effects have to be
+   * kept alive for as long as they should keep playing rather than re-timed on every call.
+   * MultiInputReader has no FF path through multiinput's RawInput backend and always returns Err.
+   */
+  fn rumble(
+    &mut self, gamepad_id: &usize, low: f32, high: f32, duration: Duration
+  ) -> Result<(), String>;
 }
 
 /**
@@ -69,69 +241,79 @@ pub trait InputReader {
  * Windows as well, but should theoretically work with Unix OS's.
  */
 pub struct GilrsInputReader {
-  gilrs: Gilrs
+  gilrs: Gilrs,
+  mapping: Mapping,
+  // The rumble effect currently bound to each gamepad, if any. Kept alive here (rather than
+  // rebuilt on every call) so it isn't dropped, and thus stopped, between rumble() calls.
+  effects: HashMap<usize, Effect>
 }
 
 impl GilrsInputReader {
-  // Constructs a GilRs input reader with an accompanying GilRs instance.
-  pub fn new() -> GilrsInputReader {
+  // Constructs a GilRs input reader with an accompanying GilRs instance, consulting the given
+  // Mapping to translate GilRs's raw buttons/axes into InputButtons/InputAxes.
+  pub fn new(mapping: Mapping) -> GilrsInputReader {
     return GilrsInputReader {
-      gilrs: Gilrs::new().unwrap()
+      gilrs: Gilrs::new().unwrap(),
+      mapping: mapping,
+      effects: HashMap::new()
     }
   }
 
-  // A helper method to convert GilRs buttons into InputButtons.
-  fn to_button(&self, button: &gilrs::Button) -> Result<InputButton, String> {
-    return match button {
-      gilrs::Button::South => Ok(InputButton::South),
-      gilrs::Button::East => Ok(InputButton::East),
-      gilrs::Button::North => Ok(InputButton::North),
-      gilrs::Button::West => Ok(InputButton::West),
-      gilrs::Button::LeftTrigger => Ok(InputButton::LeftBumper),
-      gilrs::Button::LeftTrigger2 => Ok(InputButton::LeftTrigger),
-      gilrs::Button::RightTrigger => Ok(InputButton::RightBumper),
-      gilrs::Button::RightTrigger2 => Ok(InputButton::RightTrigger),
-      gilrs::Button::Start => Ok(InputButton::Start),
-      gilrs::Button::Select => Ok(InputButton::Select),
-      gilrs::Button::DPadUp => Ok(InputButton::DPadUp),
-      gilrs::Button::DPadDown => Ok(InputButton::DPadDown),
-      gilrs::Button::DPadLeft => Ok(InputButton::DPadLeft),
-      gilrs::Button::DPadRight => Ok(InputButton::DPadRight),
-      _ => Err(format!("{:?} is currently an unmapped GilRs button.", button))
-    }
+  // A helper method to convert GilRs buttons into InputButtons via the loaded Mapping, falling
+  // back to Other(code) for anything the Mapping doesn't recognize.
+  fn to_button(&self, button: &gilrs::Button) -> InputButton {
+    return self.mapping.get_button(&format!("{:?}", button))
+      .unwrap_or(InputButton::Other(*button as u32));
   }
 
-  // A helper method to convert GilRs axes into InputAxes.
-  fn to_axis(&self, axis: &gilrs::Axis) -> Result<InputAxis, String> {
-    return match axis {
-      gilrs::Axis::LeftStickX => Ok(InputAxis::LeftX),
-      gilrs::Axis::LeftStickY => Ok(InputAxis::LeftY),
-      gilrs::Axis::RightStickX => Ok(InputAxis::RightX),
-      gilrs::Axis::RightStickY => Ok(InputAxis::RightY),
-      _ => Err(format!("{:?} is currently an unmapped GilRs axis.", axis))
+  // A helper method to convert GilRs axes into InputAxes via the loaded Mapping, falling back to
+  // Other(code) for anything the Mapping doesn't recognize.
+  fn to_axis(&self, axis: &gilrs::Axis) -> InputAxis {
+    return self.mapping.get_axis(&format!("{:?}", axis))
+      .unwrap_or(InputAxis::Other(*axis as u32));
+  }
+
+  // Same O(n) caveat as is_connected(); GilRs gives us no way to construct a GamepadId directly.
+  fn to_gamepad_id(&self, gamepad_id: &usize) -> Result<gilrs::GamepadId, String> {
+    for (id, _) in self.gilrs.gamepads() {
+      if *gamepad_id == id.try_into().unwrap() {
+        return Ok(id);
+      }
     }
+    return Err(format!("No GilRs gamepad is connected with id {}.", gamepad_id));
   }
 }
 
 impl InputReader for GilrsInputReader {
   fn read(&mut self) -> Vec<InputEvent> {
     let mut events: Vec<InputEvent> = vec!();
-    while let Some(gilrs::Event { id: gamepad_id, event: event_type, time: _ }) = self.gilrs.next_event() {
+    // axis_dpad_to_button runs gilrs's per-model remap database over raw events, turning hat/axis
+    // D-pad motion into discrete Button::DPad* changes on controllers that report it that way;
+    // without it, those controllers never emit InputButton::DPad* events at all. It needs the
+    // state gilrs.update() caches, so every filtered event has to be fed back in before we act on it.
+    while let Some(event) = self.gilrs.next_event().filter_ev(&axis_dpad_to_button, &mut self.gilrs) {
+      self.gilrs.update(&event);
+      let gilrs::Event { id: gamepad_id, event: event_type, time: _ } = event;
       match event_type {
         gilrs::EventType::ButtonChanged(button, value, _) => {
           events.push(InputEvent::GamepadButton(
             gamepad_id.try_into().unwrap(),
-            // TODO: Change this (and the axis branch) to match that of the multiinput alternative.
-            self.to_button(&button).unwrap(),
+            self.to_button(&button),
             value
           ))
         },
         gilrs::EventType::AxisChanged(axis, value, _) => {
-          events.push(InputEvent::GamepadAxis(
-            gamepad_id.try_into().unwrap(),
-            self.to_axis(&axis).unwrap(),
-            value
-          ))
+          let id: usize = gamepad_id.try_into().unwrap();
+          let mapped = self.to_axis(&axis);
+          let factor = self.mapping.get_axis_value_factor(&mapped);
+          events.push(InputEvent::GamepadAxis(id, mapped, value * factor))
+        },
+        gilrs::EventType::Connected => {
+          let name = self.gilrs.gamepad(gamepad_id).name().to_string();
+          events.push(InputEvent::GamepadConnected(gamepad_id.try_into().unwrap(), name))
+        },
+        gilrs::EventType::Disconnected => {
+          events.push(InputEvent::GamepadDisconnected(gamepad_id.try_into().unwrap()))
         },
         _ => ()
       }
@@ -150,28 +332,82 @@ impl InputReader for GilrsInputReader {
     }
     return false;
   }
+
+  /**
+   * Drives the given gamepad's rumble motors at the given low/high frequency amplitudes.
+   *
+   * A persistent effect is built the first time a gamepad rumbles and reused afterwards, since
+   * GilRs effects need to stay alive for as long as they should keep playing. The gain is updated
+   * in place on subsequent calls, and the effect is stopped and dropped outright once both
+   * amplitudes hit 0.0.
+   */
+  fn rumble(
+    &mut self, gamepad_id: &usize, low: f32, high: f32, _duration: Duration
+  ) -> Result<(), String> {
+    if low == 0.0 && high == 0.0 {
+      if let Some(effect) = self.effects.remove(gamepad_id) {
+        return effect.stop().map_err(|e| format!("Failed to stop a rumble effect: {}.", e));
+      }
+      return Ok(());
+    }
+
+    if let Some(effect) = self.effects.get(gamepad_id) {
+      let gain = low.max(high);
+      effect.set_gain(gain).map_err(|e| format!("Failed to update a rumble effect: {}.", e))?;
+      return effect.play().map_err(|e| format!("Failed to play a rumble effect: {}.", e));
+    }
+
+    let id = self.to_gamepad_id(gamepad_id)?;
+    // Built relative to the stronger motor and scaled by the same gain later calls set, so a
+    // repeat call with unchanged amplitudes leaves the output as it was.
+    let gain = low.max(high);
+    let effect = EffectBuilder::new()
+      .add_effect(BaseEffect {
+        kind: BaseEffectType::Strong { magnitude: ((low / gain) * (u16::MAX as f32)) as u16 },
+        ..Default::default()
+      })
+      .add_effect(BaseEffect {
+        kind: BaseEffectType::Weak { magnitude: ((high / gain) * (u16::MAX as f32)) as u16 },
+        ..Default::default()
+      })
+      .add_gamepad(id)
+      .finish(&mut self.gilrs)
+      .map_err(|e| format!("Failed to build a rumble effect: {}.", e))?;
+    effect.set_gain(gain).map_err(|e| format!("Failed to update a rumble effect: {}.", e))?;
+    effect.play().map_err(|e| format!("Failed to play a rumble effect: {}.", e))?;
+    self.effects.insert(*gamepad_id, effect);
+    return Ok(());
+  }
 }
 
+// multiinput has no device list or hotplug event of its own, only per-id polling via
+// get_joystick_state(), so detecting hotplugs means checking a fixed range of ids on every
+// read() and diffing against what was connected last time.
+const MULTIINPUT_MAX_GAMEPADS: usize = 4;
+
 /**
  * A struct representing a RawInput input reader that will read from the multiinput library using an
  * instance of an input manager.
- * 
+ *
  * This input reader is ONLY meant to be used for RawInput devices, and at the time of writing this,
  * has only been tested with DS4s (PS4 controllers). XInput support is poor right now and gamepads
  * other than the DS4 have not been tested. Do not expect an exquisite amount of support from this.
  */
 pub struct MultiInputReader {
-  manager: multiinput::RawInputManager
+  manager: multiinput::RawInputManager,
+  mapping: Mapping,
+  connected_ids: HashSet<usize>
 }
 
 impl MultiInputReader {
   /**
-   * Constructs a multiinput reader with an input manager instance.
-   * 
+   * Constructs a multiinput reader with an input manager instance, consulting the given Mapping
+   * to translate multiinput's raw buttons/axes into InputButtons/InputAxes.
+   *
    * This input manager instance will not read from XInput devices or mouse & keyboard, although
    * the options exist and may be implemented in a later update.
    */
-  pub fn new() -> MultiInputReader {
+  pub fn new(mapping: Mapping) -> MultiInputReader {
     let mut manager: multiinput::RawInputManager = multiinput::RawInputManager::new().unwrap();
     manager.register_devices(
       multiinput::DeviceType::Joysticks(
@@ -183,24 +419,38 @@ impl MultiInputReader {
       )
     );
     return MultiInputReader {
-      manager: manager
+      manager: manager,
+      mapping: mapping,
+      connected_ids: HashSet::new()
     }
   }
-  
-  fn to_button(&self, button: &usize) -> Result<InputButton, String> {
-    return match button {
-      0 => Ok(InputButton::West),
-      1 => Ok(InputButton::South),
-      2 => Ok(InputButton::East),
-      3 => Ok(InputButton::North),
-      4 => Ok(InputButton::LeftBumper),
-      5 => Ok(InputButton::RightBumper),
-      6 => Ok(InputButton::LeftTrigger),
-      7 => Ok(InputButton::RightTrigger),
-      8 => Ok(InputButton::Select),
-      9 => Ok(InputButton::Start),
-      _ => Err(format!("{:?} is currently an unmapped multiinput button.", button))
+
+  // Diffs the currently-connected ids against what was connected as of the last call, synthesizing
+  // GamepadConnected/GamepadDisconnected events for anything that changed.
+  fn diff_connections(&mut self) -> Vec<InputEvent> {
+    let mut events: Vec<InputEvent> = vec!();
+    let mut current_ids: HashSet<usize> = HashSet::new();
+    for gamepad_id in 0..MULTIINPUT_MAX_GAMEPADS {
+      if self.manager.get_joystick_state(gamepad_id).is_some() {
+        current_ids.insert(gamepad_id);
+        if !self.connected_ids.contains(&gamepad_id) {
+          events.push(
+            InputEvent::GamepadConnected(gamepad_id, format!("RawInput gamepad {}", gamepad_id))
+          );
+        }
+      }
     }
+    for gamepad_id in self.connected_ids.difference(&current_ids) {
+      events.push(InputEvent::GamepadDisconnected(*gamepad_id));
+    }
+    self.connected_ids = current_ids;
+    return events;
+  }
+
+  // Falls back to Other(code) for anything the Mapping doesn't recognize.
+  fn to_button(&self, button: &usize) -> InputButton {
+    return self.mapping.get_button(&button.to_string())
+      .unwrap_or(InputButton::Other(*button as u32));
   }
 
   fn to_button_value(&self, state: &multiinput::State) -> f32 {
@@ -210,28 +460,10 @@ impl MultiInputReader {
     }
   }
 
-  fn to_axis(&self, axis: &multiinput::Axis) -> Result<InputAxis, String> {
-    return match axis {
-      multiinput::Axis::X => Ok(InputAxis::LeftX),
-      multiinput::Axis::Y => Ok(InputAxis::LeftY),
-      multiinput::Axis::Z => Ok(InputAxis::RightX),
-      multiinput::Axis::RZ => Ok(InputAxis::RightY),
-      _ => Err(format!("{:?} is currently an unmapped multiinput axis.", axis))
-    }
-  }
-
-  /**
-   * A method that "corrects" a value for an axis, assuming the gamepad involved is a DS4.
-   * 
-   * For some reason, the right stick uses the Z and RZ axes; Z for horizontal and RZ for
-   * vertical. Their values also happen to be inverted, unlike the left stick. We use this
-   * method to invert the value back if it happens to be Z or RZ.
-   */
-  fn correct_axis_value(&self, axis: &multiinput::Axis, value: &f64) -> f32 {
-    return match axis {
-      multiinput::Axis::Z | multiinput::Axis::RZ => -(*value as f32),
-      _ => *value as f32
-    }
+  // Falls back to Other(code) for anything the Mapping doesn't recognize.
+  fn to_axis(&self, axis: &multiinput::Axis) -> InputAxis {
+    return self.mapping.get_axis(&format!("{:?}", axis))
+      .unwrap_or(InputAxis::Other(*axis as u32));
   }
 
   fn to_dpad(&self, hat_switch: &multiinput::HatSwitch) -> Vec<(InputButton, f32)> {
@@ -299,16 +531,10 @@ impl MultiInputReader {
     for event in buffered {
       match event {
         multiinput::event::RawEvent::JoystickButtonEvent(device_id, button, state) => {
-          match self.to_button_event(&device_id, &button, &state) {
-            Ok(mapped_event) => events.push(mapped_event),
-            Err(_) => ()
-          }
+          events.push(self.to_button_event(&device_id, &button, &state));
         },
         multiinput::event::RawEvent::JoystickAxisEvent(device_id, axis, value) => {
-          match self.to_axis_event(&device_id, &axis, &value) {
-            Ok(mapped_event) => events.push(mapped_event),
-            Err(_) => ()
-          }
+          events.push(self.to_axis_event(&device_id, &axis, &value));
         },
         multiinput::event::RawEvent::JoystickHatSwitchEvent(device_id, hat_switch) => {
           let pairs: Vec<(InputButton, f32)> = self.to_dpad(&hat_switch);
@@ -330,32 +556,20 @@ impl MultiInputReader {
 
   pub fn to_button_event(
     &self, device_id: &usize, button: &usize, state: &multiinput::State
-  ) -> Result<InputEvent, String> {
-    return match self.to_button(button) {
-      Ok(mapped) => Ok(
-        InputEvent::GamepadButton(
-          *device_id,
-          mapped,
-          self.to_button_value(state)
-        )
-      ),
-      Err(e) => Err(e)
-    }
+  ) -> InputEvent {
+    return InputEvent::GamepadButton(
+      *device_id,
+      self.to_button(button),
+      self.to_button_value(state)
+    );
   }
 
   pub fn to_axis_event(
-    &self, device_id: &usize, axis: &multiinput::Axis, value: &f64
-  ) -> Result<InputEvent, String> {
-    return match self.to_axis(axis) {
-      Ok(mapped) => Ok(
-        InputEvent::GamepadAxis(
-          *device_id,
-          mapped,
-          self.correct_axis_value(axis, value)
-        )
-      ),
-      Err(e) => Err(e)
-    }
+    &mut self, device_id: &usize, axis: &multiinput::Axis, value: &f64
+  ) -> InputEvent {
+    let mapped = self.to_axis(axis);
+    let factor = self.mapping.get_axis_value_factor(&mapped);
+    return InputEvent::GamepadAxis(*device_id, mapped, (*value as f32) * factor);
   }
 }
 
@@ -363,12 +577,22 @@ impl InputReader for MultiInputReader {
   fn read(&mut self) -> Vec<InputEvent> {
     let mut buffered: Vec<RawEvent> = vec!();
     while let Some(event) = self.manager.get_event() {
-      buffered.push(event); 
+      buffered.push(event);
     }
-    return self.parse_buffered(buffered);
+    let mut events: Vec<InputEvent> = self.parse_buffered(buffered);
+    events.append(&mut self.diff_connections());
+    return events;
   }
 
   fn is_connected(&mut self, gamepad_id: &usize) -> bool {
     return self.manager.get_joystick_state(*gamepad_id).is_some();
   }
+
+  // multiinput's RawInput backend has no force-feedback API, so rumble can't be forwarded for
+  // gamepads read through this fallback.
+  fn rumble(
+    &mut self, _gamepad_id: &usize, _low: f32, _high: f32, _duration: Duration
+  ) -> Result<(), String> {
+    return Err("Rumble is not supported through the RawInput fallback.".to_string());
+  }
 }
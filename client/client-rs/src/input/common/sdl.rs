@@ -1,6 +1,6 @@
 extern crate sdl2;
 
-use crate::input::common::reader::{
+use crate::input::{
   InputButton,
   InputAxis,
   InputEvent,
@@ -21,6 +21,7 @@ use sdl2::{
 };
 
 use std::collections::HashMap;
+use std::time::Duration;
 
 pub struct SdlAdapter {
   gamepads: HashMap<u32, GameController>,
@@ -31,11 +32,25 @@ pub struct SdlAdapter {
 }
 
 impl SdlAdapter {
-  pub fn new() -> SdlAdapter {
+  /**
+   * Constructs an SDL adapter, optionally loading a gamecontrollerdb.txt-style mapping file
+   * first so controllers SDL doesn't recognize out of the box (and buttons like Guide, which SDL
+   * otherwise never reports for them) come in properly mapped instead of going unrecognized.
+   *
+   * A missing or malformed file is logged and otherwise ignored; SDL's built-in mappings still
+   * apply, so this is never fatal to starting up.
+   */
+  pub fn new(controller_db_path: Option<&str>) -> SdlAdapter {
     let sdl_context: Sdl = sdl2::init().unwrap();
 
     let game_controller: GameControllerSubsystem =
       sdl_context.game_controller().unwrap();
+    if let Some(path) = controller_db_path {
+      match game_controller.load_mappings(path) {
+        Err(e) => println!("Failed to load controller mappings from {}: {}.", path, e),
+        Ok(_) => ()
+      }
+    }
     let event_pump: EventPump = sdl_context.event_pump().unwrap();
     let video: VideoSubsystem = sdl_context.video().unwrap();
     return SdlAdapter {
@@ -82,6 +97,8 @@ impl SdlAdapter {
       Button::DPadDown => Ok(InputButton::DPadDown),
       Button::DPadLeft => Ok(InputButton::DPadLeft),
       Button::DPadRight => Ok(InputButton::DPadRight),
+      Button::Guide => Ok(InputButton::Guide),
+      Button::Misc1 => Ok(InputButton::Capture),
       _ => Err(
         format!("{:?} is currently an unmapped SDL button.", button)
       )
@@ -165,10 +182,13 @@ impl InputReader for SdlAdapter {
           // We need to store the gamepad somewhere to receive button events.
           let gamepad: GameController = self.game_controller.open(which)
             .unwrap();
+          let name = gamepad.name();
           self.gamepads.insert(which, gamepad);
+          events.push(InputEvent::GamepadConnected(which as usize, name));
         },
         Event::ControllerDeviceRemoved { which, .. } => {
           self.gamepads.remove(&which);
+          events.push(InputEvent::GamepadDisconnected(which as usize));
         },
         Event::ControllerAxisMotion { timestamp: _, which, axis, value } => {
           if self.is_trigger(&axis) {
@@ -204,4 +224,19 @@ impl InputReader for SdlAdapter {
   fn is_connected(&mut self, gamepad_id: &usize) -> bool {
     return (&mut self.game_controller).open(*gamepad_id as u32).is_ok();
   }
+
+  // Drives the stored GameController's built-in rumble motors directly; SDL exposes this on the
+  // controller itself, so there's no need to go through a separate haptic device handle.
+  fn rumble(
+    &mut self, gamepad_id: &usize, low: f32, high: f32, duration: Duration
+  ) -> Result<(), String> {
+    return match self.gamepads.get_mut(&(*gamepad_id as u32)) {
+      Some(gamepad) => gamepad.set_rumble(
+        (low * (u16::MAX as f32)) as u16,
+        (high * (u16::MAX as f32)) as u16,
+        duration.as_millis() as u32
+      ).map_err(|e| format!("Failed to rumble gamepad (id: {}): {}.", gamepad_id, e)),
+      None => Err(format!("No SDL gamepad is stored with id {}.", gamepad_id))
+    }
+  }
 }
@@ -1,15 +1,12 @@
 pub mod client;
 pub mod config;
 pub mod input;
+pub mod pad;
 
 use crate::{
-  input::adapter::{
-    gilrs::GilrsAdapter,
-    multiinput::MultiInputAdapter,
-    sdl::SdlAdapter
-  },
+  input::common::sdl::SdlAdapter,
   client::Client,
-  config::Config, 
+  config::Config,
 };
 use clap::{Arg, App, ArgMatches};
 use crossbeam_channel::{bounded, tick, Receiver, select};
@@ -48,10 +45,12 @@ fn main() -> Result<(), ctrlc::Error> {
   let config: Config = confy::load_path("./config.toml")
     .expect("Expected a config to be generated from a file.");
 
+  let input_adapter = SdlAdapter::new(config.get_controller_db_path().as_deref());
+
   let mut client: Client = Client::new(
     config,
-    // Box::new(GilrsAdapter::new()),
-    Box::new(SdlAdapter::new())
+    // Box::new(GilrsInputReader::new(Mapping::default_gilrs())),
+    Box::new(input_adapter)
   );
   client.set_server_ip(server_ip);
 
@@ -68,7 +67,7 @@ fn main() -> Result<(), ctrlc::Error> {
   loop {
     select! {
       recv(ticks) -> _ => {
-        client.update_all_pads();
+        client.update_pads();
         match client.update_server() {
           Err(e) => {
             println!("An error occurred while attempting to update the input server:");
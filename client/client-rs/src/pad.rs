@@ -3,6 +3,7 @@ use crate::input::{
   InputAxis,
   InputEvent
 };
+use crate::config::StickConfig;
 use gilrs::{
   Gilrs,
   EventType,
@@ -11,6 +12,8 @@ use gilrs::{
   Button
 };
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::time;
 
 // An enum representing the different Switch controllers that can be emulated.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -62,7 +65,7 @@ impl SwitchPad {
 }
 
 // An enum representing all the different buttons on a Switch controller.
-// TODO: What about the home button?
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SwitchButton {
   A,
   B,
@@ -91,13 +94,14 @@ pub enum SwitchButton {
   SLL,
   SRL,
   SLR,
-  SRR
+  SRR,
+  Home,
+  Capture
 }
 
 impl SwitchButton {
   // Returns the bit corresponding to this button.
   pub fn value(&self) -> i32 {
-    // TODO: What about the home button?
     match self {
       Self::A => return 1,
       Self::B => return 1 << 1,
@@ -126,26 +130,58 @@ impl SwitchButton {
       Self::SLL => return 1 << 24,
       Self::SRL => return 1 << 25,
       Self::SLR => return 1 << 26,
-      Self::SRR => return 1 << 27
+      Self::SRR => return 1 << 27,
+      Self::Home => return 1 << 28,
+      Self::Capture => return 1 << 29
     }
   }
 
-  // Maps an input event button to a Switch button depending on the specified pad type.
-  pub fn map_button(button: &InputButton, switch_pad: &SwitchPad) -> Result<SwitchButton, String> {
+  /**
+   * Maps an input event button to a Switch button depending on the specified pad type.
+   *
+   * If a ButtonProfile is active for this pad, its override is consulted first; only buttons it
+   * doesn't bind fall through to the built-in default mapping below.
+   */
+  pub fn map_button(
+    button: &InputButton, switch_pad: &SwitchPad, profile: Option<&ButtonProfile>
+  ) -> Result<SwitchButton, String> {
+    if let Some(profile) = profile {
+      if let Some(switch_button) = profile.get(button) {
+        return Ok(*switch_button);
+      }
+    }
+    return Self::map_default(button, switch_pad);
+  }
+
+  // The built-in input button -> Switch button mapping used when no profile override applies.
+  fn map_default(button: &InputButton, switch_pad: &SwitchPad) -> Result<SwitchButton, String> {
     match button {
       InputButton::DPadUp => Ok(Self::DU),
       InputButton::DPadRight => Ok(Self::DR),
       InputButton::DPadDown => Ok(Self::DD),
       InputButton::DPadLeft => Ok(Self::DL),
       
-      InputButton::LeftBumper => Ok(Self::L),
-      InputButton::RightBumper => Ok(Self::R),
+      // On a sideways JoyCon, the shoulder buttons are SL/SR rather than L/R; which physical
+      // JoyCon (left or right) decides which pair of SwitchButton bits gets set.
+      InputButton::LeftBumper => match switch_pad {
+        SwitchPad::ProController => return Ok(Self::L),
+        SwitchPad::JoyConLSide => return Ok(Self::SLL),
+        SwitchPad::JoyConRSide => return Ok(Self::SLR)
+      },
+      InputButton::RightBumper => match switch_pad {
+        SwitchPad::ProController => return Ok(Self::R),
+        SwitchPad::JoyConLSide => return Ok(Self::SRL),
+        SwitchPad::JoyConRSide => return Ok(Self::SRR)
+      },
       InputButton::LeftTrigger => Ok(Self::ZL),
       InputButton::RightTrigger => Ok(Self::ZR),
 
       InputButton::Start => Ok(Self::Plus),
       InputButton::Select => Ok(Self::Minus),
-      
+
+      InputButton::Guide => Ok(Self::Home),
+      InputButton::Capture => Ok(Self::Capture),
+
       InputButton::North => match switch_pad {
         SwitchPad::ProController => return Ok(Self::X),
         SwitchPad::JoyConLSide => return Ok(Self::DR),
@@ -171,6 +207,82 @@ impl SwitchButton {
   }
 }
 
+/**
+ * A struct representing a user-configurable table of input button -> Switch button overrides.
+ *
+ * Profiles are meant to be loaded per slot from Config, so users can swap A/B, remap triggers,
+ * etc. without touching the built-in default mapping in SwitchButton::map_default().
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ButtonProfile {
+  bindings: HashMap<InputButton, SwitchButton>
+}
+
+impl ButtonProfile {
+  pub fn new() -> ButtonProfile {
+    return ButtonProfile {
+      bindings: HashMap::new()
+    }
+  }
+
+  pub fn get(&self, button: &InputButton) -> Option<&SwitchButton> {
+    return self.bindings.get(button);
+  }
+
+  pub fn bind(&mut self, button: InputButton, switch_button: SwitchButton) -> () {
+    self.bindings.insert(button, switch_button);
+  }
+}
+
+// A behavior a mapped Switch button can be given on top of just following the physical button.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum ButtonMode {
+  Normal,
+  // Each physical press flips a latched state instead of following the button directly.
+  Toggle,
+  // While held, the emitted bit pulses on/off at the given rate in Hz.
+  Turbo(f32)
+}
+
+// A per-slot table of Switch button -> behavior, consulted every tick to resolve modifiers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ModeProfile {
+  modes: HashMap<SwitchButton, ButtonMode>
+}
+
+impl ModeProfile {
+  pub fn new() -> ModeProfile {
+    return ModeProfile {
+      modes: HashMap::new()
+    }
+  }
+
+  pub fn get(&self, switch_button: &SwitchButton) -> ButtonMode {
+    return *self.modes.get(switch_button).unwrap_or(&ButtonMode::Normal);
+  }
+
+  pub fn set(&mut self, switch_button: SwitchButton, mode: ButtonMode) -> () {
+    self.modes.insert(switch_button, mode);
+  }
+}
+
+// Per-button edge/phase tracking needed to resolve toggle and turbo modifiers each tick.
+struct ModifierState {
+  was_physical: bool,
+  toggled: bool,
+  phase_start: Option<time::Instant>
+}
+
+impl ModifierState {
+  fn new() -> ModifierState {
+    return ModifierState {
+      was_physical: false,
+      toggled: false,
+      phase_start: None
+    }
+  }
+}
+
 /**
  * A struct representing an emulated Switch controller.
  * 
@@ -186,7 +298,29 @@ pub struct EmulatedPad {
   switch_pad: Option<SwitchPad>,
   keyout: i32,
   left: (i32, i32),
-  right: (i32, i32)
+  right: (i32, i32),
+  left_raw: (f32, f32),
+  right_raw: (f32, f32),
+  stick_config: StickConfig,
+
+  // The low/high frequency rumble amplitudes (0.0-1.0) most recently reported by the Switch for
+  // this slot, if any. This is just storage; the client is responsible for actually driving the
+  // physical gamepad through the input adapter's rumble() method.
+  rumble: Option<(f32, f32)>,
+
+  // The raw physical press state of every mapped button, keyed by its mapped Switch button. The
+  // actual keyout bit is computed by resolve_modifiers(), since turbo/toggle buttons need to be
+  // re-evaluated every tick rather than just on a raw input change.
+  raw_buttons: HashMap<SwitchButton, bool>,
+  mode_profile: ModeProfile,
+  modifier_states: HashMap<SwitchButton, ModifierState>,
+
+  // The active button remap override for this pad, if any.
+  profile: Option<ButtonProfile>,
+
+  // Set once this pad's gamepad has been reported disconnected, for the one tick it takes to
+  // report a con_type of 0 to the Switch before soft_disconnect() actually frees the slot.
+  pending_disconnect: bool
 }
 
 impl EmulatedPad {
@@ -197,10 +331,34 @@ impl EmulatedPad {
       switch_pad: None,
       keyout: 0,
       left: (0, 0),
-      right: (0, 0)
+      right: (0, 0),
+      left_raw: (0.0, 0.0),
+      right_raw: (0.0, 0.0),
+      stick_config: StickConfig::default(),
+      rumble: None,
+      raw_buttons: HashMap::new(),
+      mode_profile: ModeProfile::new(),
+      modifier_states: HashMap::new(),
+      profile: None,
+      pending_disconnect: false
     }
   }
 
+  // Sets this pad's stick deadzone/response curve, consulted the next time an axis is updated.
+  pub fn set_stick_config(&mut self, stick_config: StickConfig) -> () {
+    self.stick_config = stick_config;
+  }
+
+  // Sets this pad's turbo/toggle modifiers, consulted every tick by resolve_modifiers().
+  pub fn set_mode_profile(&mut self, mode_profile: ModeProfile) -> () {
+    self.mode_profile = mode_profile;
+  }
+
+  // Sets the button remap override this pad should consult before falling back to the defaults.
+  pub fn set_profile(&mut self, profile: Option<ButtonProfile>) -> () {
+    self.profile = profile;
+  }
+
   pub fn get_gamepad_id(&self) -> &Option<usize> {
     return &self.gamepad_id;
   }
@@ -209,6 +367,15 @@ impl EmulatedPad {
     return &self.switch_pad;
   }
 
+  // The switch pad type as it should be reported to the Switch: None while pending_disconnect is
+  // set, even though switch_pad itself isn't cleared until the handshake's second tick.
+  pub fn get_packed_switch_pad(&self) -> Option<SwitchPad> {
+    if self.pending_disconnect {
+      return None;
+    }
+    return self.switch_pad;
+  }
+
   pub fn get_keyout(&self) -> &i32 {
     return &self.keyout;
   }
@@ -221,17 +388,58 @@ impl EmulatedPad {
     return &self.right;
   }
 
+  pub fn get_rumble(&self) -> &Option<(f32, f32)> {
+    return &self.rumble;
+  }
+
+  // Records the rumble amplitudes the Switch most recently asked this slot to play. Passing None
+  // clears it; the client then drives the physical gamepad with (0.0, 0.0), stopping it.
+  pub fn set_rumble(&mut self, rumble: Option<(f32, f32)>) -> () {
+    self.rumble = rumble;
+  }
+
   pub fn connect(&mut self, gamepad_id: &usize, switch_pad: SwitchPad) -> () {
     self.gamepad_id = Some(*gamepad_id);
     self.switch_pad = Some(switch_pad);
+    self.pending_disconnect = false;
   }
 
-  // TODO: Use this to "disconnect" the pad when it's been disconnected by the Switch?
-  // This pad will still be considered "connected" though, so you'll have to change the logic
-  // surrounding that (like in self.is_connected()) to switch this back to a useable state.
-  // Actually, this might be better off done in the client rather than here in the emulated pad.
+  /**
+   * Marks this pad as about to be disconnected, without freeing its slot yet.
+   *
+   * The Switch needs to see a con_type of 0 for a tick before it'll actually drop a controller,
+   * so a disconnect is a two-tick handshake: begin_disconnect() flags the slot so packing reports
+   * it as gone, and the following tick's soft_disconnect() actually frees the slot for reuse.
+   */
+  pub fn begin_disconnect(&mut self) -> () {
+    self.pending_disconnect = true;
+  }
+
+  pub fn is_pending_disconnect(&self) -> bool {
+    return self.pending_disconnect;
+  }
+
+  /**
+   * Actually frees this pad's slot, to be called on the tick after begin_disconnect() so the
+   * Switch has already seen a con_type of 0 for this slot.
+   *
+   * Everything carried over from the disconnected controller is reset here too, not just the
+   * connection bookkeeping: once this slot is reused by a different physical controller, any
+   * button left "held", turbo/toggle state latched, or stale stick position at the moment of
+   * disconnect would otherwise bleed into the new controller's first tick.
+   */
   pub fn soft_disconnect(&mut self) -> () {
+    self.gamepad_id = None;
     self.switch_pad = None;
+    self.rumble = None;
+    self.keyout = 0;
+    self.left = (0, 0);
+    self.right = (0, 0);
+    self.left_raw = (0.0, 0.0);
+    self.right_raw = (0.0, 0.0);
+    self.raw_buttons.clear();
+    self.modifier_states.clear();
+    self.pending_disconnect = false;
   }
 
   // Attempts to update this pad using a GilRs event. Events are passed from the client and/or a
@@ -239,36 +447,118 @@ impl EmulatedPad {
   pub fn update(&mut self, event: &InputEvent) -> () {
     match event {
       InputEvent::GamepadButton(_, button, value) => self.update_keyout(button, value),
-      InputEvent::GamepadAxis(_, axis, value) => self.update_axis(axis, value)
+      InputEvent::GamepadAxis(_, axis, value) => self.update_axis(axis, value),
+      // Handled directly by the client (see Client::parse_events()) before a pad ever sees it.
+      InputEvent::GamepadConnected(_, _) => (),
+      InputEvent::GamepadDisconnected(_) => ()
     }
   }
 
-  // Attempt to update the keyout for a button and its corresponding value.
+  // Attempt to record the physical press state for a button, keyed by its mapped Switch button.
+  // The actual keyout bit is computed later by resolve_modifiers(), since turbo/toggle buttons
+  // need to be re-evaluated every tick rather than just on a raw input change.
   pub fn update_keyout(&mut self, button: &InputButton, value: &f32) -> () {
     if self.switch_pad.is_some() {
       match &SwitchButton::map_button(
         button,
-        &self.switch_pad.as_ref().unwrap()
+        &self.switch_pad.as_ref().unwrap(),
+        self.profile.as_ref()
       ) {
-        Ok(switch_button) => self.set_del_bit(
-          &switch_button.value(),
-          &(*value as i32)
-        ),
+        Ok(switch_button) => {
+          self.raw_buttons.insert(*switch_button, *value != 0.0);
+        },
         Err(_) => ()
       }
     }
   }
 
+  /**
+   * Resolves every mapped button's raw physical state into the bit that should actually be sent,
+   * applying this pad's turbo/toggle modifiers, and rewrites keyout from scratch.
+   *
+   * Must be called once per tick (from the client's fixed-interval loop) so turbo pulsing and
+   * toggle edge-detection stay timed off elapsed wall-clock time rather than input events, which
+   * don't fire again while a button is just being held down.
+   */
+  pub fn resolve_modifiers(&mut self) -> () {
+    let raw_buttons: Vec<(SwitchButton, bool)> = self.raw_buttons.iter()
+      .map(|(switch_button, physical)| (*switch_button, *physical))
+      .collect();
+
+    for (switch_button, physical) in raw_buttons {
+      let state = self.modifier_states.entry(switch_button).or_insert_with(ModifierState::new);
+      let was_physical: bool = state.was_physical;
+      state.was_physical = physical;
+
+      let bit_on: bool = match self.mode_profile.get(&switch_button) {
+        ButtonMode::Normal => physical,
+        ButtonMode::Toggle => {
+          if physical && !was_physical {
+            state.toggled = !state.toggled;
+          }
+          state.toggled
+        },
+        ButtonMode::Turbo(hz) => {
+          if physical {
+            let phase_start: &time::Instant = state.phase_start.get_or_insert_with(time::Instant::now);
+            let elapsed: f32 = phase_start.elapsed().as_secs_f32();
+            // Each full cycle (on then off) takes 1/hz seconds.
+            ((elapsed * hz * 2.0) as u64) % 2 == 0
+          } else {
+            state.phase_start = None;
+            false
+          }
+        }
+      };
+
+      self.set_del_bit(&switch_button.value(), &(bit_on as i32));
+    }
+  }
+
   // Attempt to update the stick state for an axis and its corresponding value.
   pub fn update_axis(&mut self, axis: &InputAxis, value: &f32) -> () {
-    let converted: i32 = (*value * 32767.0) as i32;
     match axis {
-      InputAxis::LeftX => self.left.0 = converted,
-      InputAxis::LeftY => self.left.1 = converted,
-      InputAxis::RightX => self.right.0 = converted,
-      InputAxis::RightY => self.right.1 = converted
+      InputAxis::LeftX => {
+        self.left_raw.0 = *value;
+        self.left = Self::apply_deadzone(self.left_raw, &self.stick_config);
+      },
+      InputAxis::LeftY => {
+        self.left_raw.1 = *value;
+        self.left = Self::apply_deadzone(self.left_raw, &self.stick_config);
+      },
+      InputAxis::RightX => {
+        self.right_raw.0 = *value;
+        self.right = Self::apply_deadzone(self.right_raw, &self.stick_config);
+      },
+      InputAxis::RightY => {
+        self.right_raw.1 = *value;
+        self.right = Self::apply_deadzone(self.right_raw, &self.stick_config);
+      },
+      // Not a recognized stick axis; nothing here to update.
+      InputAxis::Other(_) => ()
     }
-    println!("{:?}", self.left);
+  }
+
+  /**
+   * Applies a radial deadzone and response curve to a stick's raw (x, y) values, rather than
+   * gating each axis independently, so diagonal drift/deflection is handled consistently.
+   */
+  fn apply_deadzone(raw: (f32, f32), stick_config: &StickConfig) -> (i32, i32) {
+    let (x, y): (f32, f32) = raw;
+    let mag: f32 = (x * x + y * y).sqrt();
+    if mag < stick_config.get_inner_dz() {
+      return (0, 0);
+    }
+
+    let scaled: f32 = ((mag - stick_config.get_inner_dz())
+      / (stick_config.get_outer_dz() - stick_config.get_inner_dz()))
+      .clamp(0.0, 1.0)
+      .powf(stick_config.get_curve());
+
+    return (
+      ((x / mag) * scaled * 32767.0) as i32,
+      ((y / mag) * scaled * 32767.0) as i32
+    );
   }
 
   // Updates the keyout using a bitwise OR if an input value isn't 0, otherwise a bitwise AND using
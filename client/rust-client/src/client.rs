@@ -1,8 +1,10 @@
+use crate::config::Config;
 use crate::input::{SwitchPad, EmulatedPad};
 
 use gilrs::{
   Gilrs,
   Event,
+  EventType,
   GamepadId,
   Button
 };
@@ -20,33 +22,80 @@ use std::{
  * - A list of emulated pads.
  */
 pub struct Client {
+  config: Config,
   gilrs: Gilrs,
   pads: Vec<EmulatedPad>,
 }
 
 impl Client {
-  // Constructs a client with a GilRs instance and a fixed amount of disconnected emulated pads.
-  pub fn new() -> Client {
+  /**
+   * Constructs a client with a GilRs instance and a fixed amount of disconnected emulated pads,
+   * with each pad's stick deadzone/curve seeded from the given config.
+   */
+  pub fn new(config: Config) -> Client {
+    let stick_configs = config.stick_configs_to_vec();
+    let mut pads: Vec<EmulatedPad> = c![EmulatedPad::new(), for i in 0..4];
+    for (i, pad) in pads.iter_mut().enumerate() {
+      pad.set_stick_config(stick_configs[i]);
+    }
     return Client {
+      config: config,
       gilrs: Gilrs::new().unwrap(),
-      pads: c![EmulatedPad::new(), for i in 0..4]
+      pads: pads
     }
   }
- 
+
   /**
    * A method that attempts to assign the given gamepad id and switch pad type to an open slot.
    * If there's no open slot, we return an error.
+   *
+   * The assigned slot's button profile is picked by first checking for an override bound to the
+   * gamepad's UUID, falling back to that slot's configured default profile otherwise.
    */
-  fn assign_pad(&mut self, gamepad_id: &GamepadId, switch_pad: SwitchPad) -> Result<&str, &str> {
-    let mut slot: i8 = 1;
-    for pad in &mut self.pads {
-      if !pad.is_connected(&mut self.gilrs) {
-        pad.connect(gamepad_id, switch_pad);
-        return Ok(format!("Gamepad (id: {}) connected to slot {}", gamepad_id, slot));
+  fn assign_pad(&mut self, gamepad_id: &GamepadId, switch_pad: SwitchPad) -> Result<String, String> {
+    let gamepad = self.gilrs.gamepad(*gamepad_id);
+    let name: String = gamepad.name().to_string();
+    let uuid: String = gamepad.uuid()
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect();
+    let button_profiles = self.config.button_profiles_to_vec();
+    let mode_profiles = self.config.mode_profiles_to_vec();
+
+    // If this gamepad has a remembered slot from a previous connection and that slot is free,
+    // prefer it over the first open slot so multi-controller setups stay predictable.
+    let preferred: Option<usize> = self.config.get_uuid_slot(&uuid)
+      .filter(|&i| !self.pads[i].is_connected(&mut self.gilrs));
+
+    let order: Vec<usize> = match preferred {
+      Some(i) => vec!(i),
+      None => (0..self.pads.len()).collect()
+    };
+    for i in order {
+      if !self.pads[i].is_connected(&mut self.gilrs) {
+        self.pads[i].connect(gamepad_id, switch_pad);
+        self.pads[i].set_profile(
+          self.config.get_uuid_profile(&uuid)
+            .cloned()
+            .or_else(|| button_profiles[i].clone())
+        );
+        if let Some(mode_profile) = mode_profiles[i].clone() {
+          self.pads[i].set_mode_profile(mode_profile);
+        }
+        return Ok(
+          format!(
+            "Gamepad \"{}\" (uuid: {}, id: {}) connected to slot {}",
+            name, uuid, gamepad_id, i + 1
+          )
+        );
       }
-      slot = slot + 1;
     }
-    return Err("Couldn't assign controller since there were no slots available.")
+    return Err(
+      format!(
+        "Couldn't assign gamepad \"{}\" (uuid: {}, id: {}) since there were no slots available.",
+        name, uuid, gamepad_id
+      )
+    )
   }
 
   /**
@@ -58,10 +107,25 @@ impl Client {
    */
   pub fn start(&mut self, ip: &str, online: bool) -> () {
     // 0.0.0.0 will be bound to localhost, don't worry
-    let sock: UdpSocket = UdpSocket::bind("0.0.0.0:8000").unwrap(); 
+    let sock: UdpSocket = UdpSocket::bind("0.0.0.0:8000").unwrap();
+    // Reading feedback packets must never block the input loop, so we just poll for one on
+    // every tick and move on if there isn't one waiting.
+    sock.set_nonblocking(true).unwrap();
     loop {
+      self.poll_feedback(&sock);
       while let Some(Event { id: gamepad_id, event, time: _ }) = self.gilrs.next_event() {
-        let mut gamepad_mapped: bool = false; 
+        if let EventType::Disconnected = event {
+          let name: String = self.gilrs.gamepad(gamepad_id).name().to_string();
+          for pad in &mut self.pads {
+            if *pad.get_gamepad_id() == Some(gamepad_id) {
+              pad.soft_disconnect();
+              println!("Gamepad \"{}\" (id: {}) disconnected.", name, gamepad_id);
+            }
+          }
+          continue;
+        }
+
+        let mut gamepad_mapped: bool = false;
         for pad in &mut self.pads {
           if pad.is_connected(&mut self.gilrs)
           && pad.get_gamepad_id().unwrap() == gamepad_id {
@@ -85,10 +149,19 @@ impl Client {
           } 
         }
       }
+      for pad in &mut self.pads {
+        pad.resolve_modifiers();
+      }
+      if self.config.get_battery_passthrough() {
+        for pad in &mut self.pads {
+          pad.update_power(&mut self.gilrs);
+        }
+      }
       if online {
         let connected: i8 = self.get_connected();
         match sock.send_to(
-          &PackedData::new(&self.pads, connected).to_bytes(),
+          &PackedData::new(&self.pads, connected, self.config.get_battery_passthrough())
+            .to_bytes(),
           format!("{}:8000", ip)
         ) {
           Err(e) => println!("{}", e),
@@ -99,6 +172,37 @@ impl Client {
     }
   }
 
+  /**
+   * A method that reads every buffered rumble packet, if any, and drives the matching pad's
+   * gamepad through each one.
+   *
+   * The server sends one packet per active slot, shaped as a raw `[slot: u8, low_freq_amp: u16,
+   * high_freq_amp: u16]` (5 bytes, little-endian). This is kept separate from the send in start()
+   * since these packets come from the Switch rather than going to it.
+   * buf is reset every iteration so a short or malformed datagram can't leave stale bytes from a
+   * prior packet to be parsed as if they were part of this one.
+   */
+  fn poll_feedback(&mut self, sock: &UdpSocket) -> () {
+    loop {
+      let mut buf: [u8; 5] = [0; 5];
+      let len = match sock.recv_from(&mut buf) {
+        Ok((len, _)) => len,
+        Err(_) => break
+      };
+      if len != buf.len() {
+        println!("Ignoring a malformed feedback packet ({} bytes, expected {}).", len, buf.len());
+        continue;
+      }
+
+      let slot: usize = buf[0] as usize;
+      let low_freq_amp: u16 = u16::from_le_bytes([buf[1], buf[2]]);
+      let high_freq_amp: u16 = u16::from_le_bytes([buf[3], buf[4]]);
+      if let Some(pad) = self.pads.get_mut(slot) {
+        pad.update_rumble(&mut self.gilrs, low_freq_amp, high_freq_amp);
+      }
+    }
+  }
+
   // A method that returns the number of pads connected to this client.
   fn get_connected(&mut self) -> i8 {
     let mut connected: i8 = 0;
@@ -148,6 +252,11 @@ pub struct PackedData {
   joy_l_y4: i32,
   joy_r_x4: i32,
   joy_r_y4: i32,
+
+  // Only appended to the packet (and only under the 0x3277 magic) when battery passthrough is
+  // enabled, since older servers don't expect the extra trailing bytes.
+  battery_passthrough: bool,
+  power: [u8; 4]
 }
 
 // Maps a switch pad (or lack thereof) to its integer counterpart.
@@ -160,9 +269,9 @@ fn switch_pad_to_int(switch_pad: &Option<SwitchPad>) -> i8 {
 
 impl PackedData {
   // Constructs a packed data struct just from a list of pads.
-  pub fn new(pads: &Vec<EmulatedPad>, connected: i8) -> PackedData {
+  pub fn new(pads: &Vec<EmulatedPad>, connected: i8, battery_passthrough: bool) -> PackedData {
     return PackedData {
-      magic: 0x3276,
+      magic: if battery_passthrough { 0x3277 } else { 0x3276 },
       connected: connected as u16,
 
       con_type: switch_pad_to_int(pads[0].get_switch_pad()) as u16,
@@ -192,17 +301,26 @@ impl PackedData {
       joy_l_y4: pads[3].get_left().1,
       joy_r_x4: pads[3].get_right().0,
       joy_r_y4: pads[3].get_right().1,
+
+      battery_passthrough: battery_passthrough,
+      power: [
+        pads[0].get_power(),
+        pads[1].get_power(),
+        pads[2].get_power(),
+        pads[3].get_power()
+      ]
     }
   }
 
-  // Converts this packed data to structured bytes.
+  // Converts this packed data to structured bytes, appending the per-slot battery bytes only when
+  // battery passthrough is enabled.
   pub fn to_bytes(&self) -> Vec<u8> {
-    /* 
+    /*
      * H - SwitchPad (Controller Type)
      * Q - Keyout
-     * i - Stick Info 
+     * i - Stick Info
      */
-    structure!("<HHHQiiiiHQiiiiHQiiiiHQiiii").pack(
+    let mut bytes: Vec<u8> = structure!("<HHHQiiiiHQiiiiHQiiiiHQiiii").pack(
       self.magic,
       self.connected,
 
@@ -233,6 +351,11 @@ impl PackedData {
       self.joy_l_y4,
       self.joy_r_x4,
       self.joy_r_y4,
-    ).unwrap()
+    ).unwrap();
+
+    if self.battery_passthrough {
+      bytes.extend_from_slice(&self.power);
+    }
+    return bytes;
   }
 }
\ No newline at end of file
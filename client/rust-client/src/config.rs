@@ -1,12 +1,82 @@
-use crate::input::SwitchPad;
+use crate::input::{ButtonProfile, ModeProfile, SwitchPad};
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/**
+ * A struct representing a stick's radial deadzone and response curve.
+ *
+ * - inner_dz is the normalized magnitude below which the stick is treated as neutral, meant to
+ *   swallow the drift cheap sticks send at rest.
+ * - outer_dz is the magnitude at (or past) which the stick is treated as fully deflected.
+ * - curve is a sensitivity exponent applied to the rescaled magnitude; values above 1.0 give
+ *   finer control near the center at the cost of feeling "slower" overall.
+ */
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct StickConfig {
+  inner_dz: f32,
+  outer_dz: f32,
+  curve: f32
+}
+
+impl Default for StickConfig {
+  fn default() -> StickConfig {
+    return StickConfig {
+      inner_dz: 0.1,
+      outer_dz: 1.0,
+      curve: 1.0
+    }
+  }
+}
+
+impl StickConfig {
+  pub fn get_inner_dz(&self) -> f32 {
+    return self.inner_dz;
+  }
+
+  pub fn get_outer_dz(&self) -> f32 {
+    return self.outer_dz;
+  }
+
+  pub fn get_curve(&self) -> f32 {
+    return self.curve;
+  }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
   switch_pad_1: Option<SwitchPad>,
   switch_pad_2: Option<SwitchPad>,
   switch_pad_3: Option<SwitchPad>,
-  switch_pad_4: Option<SwitchPad>
+  switch_pad_4: Option<SwitchPad>,
+
+  stick_config_1: StickConfig,
+  stick_config_2: StickConfig,
+  stick_config_3: StickConfig,
+  stick_config_4: StickConfig,
+
+  // Per-slot button remap overrides, consulted before the built-in default mapping.
+  button_profile_1: Option<ButtonProfile>,
+  button_profile_2: Option<ButtonProfile>,
+  button_profile_3: Option<ButtonProfile>,
+  button_profile_4: Option<ButtonProfile>,
+
+  // Per-slot turbo/toggle modifiers, applied on top of whichever button profile is active.
+  mode_profile_1: Option<ModeProfile>,
+  mode_profile_2: Option<ModeProfile>,
+  mode_profile_3: Option<ModeProfile>,
+  mode_profile_4: Option<ModeProfile>,
+
+  // Button remap overrides keyed by a gamepad's GilRs UUID (hex-encoded), so a given physical pad
+  // gets the same layout no matter which slot it's assigned to.
+  uuid_profiles: HashMap<String, ButtonProfile>,
+
+  // A gamepad's GilRs UUID (hex-encoded) mapped to the slot it should prefer on (re)connection,
+  // so a given physical pad lands in the same player slot across reconnects.
+  uuid_slots: HashMap<String, usize>,
+
+  // Whether to append per-slot battery levels to the packet (under the 0x3277 magic) so the
+  // Switch can show the correct battery icon. Off by default since it requires a newer server.
+  battery_passthrough: bool
 }
 
 impl Default for Config {
@@ -15,12 +85,32 @@ impl Default for Config {
       switch_pad_1: Some(SwitchPad::ProController),
       switch_pad_2: Some(SwitchPad::ProController),
       switch_pad_3: Some(SwitchPad::ProController),
-      switch_pad_4: Some(SwitchPad::ProController)
+      switch_pad_4: Some(SwitchPad::ProController),
+
+      stick_config_1: StickConfig::default(),
+      stick_config_2: StickConfig::default(),
+      stick_config_3: StickConfig::default(),
+      stick_config_4: StickConfig::default(),
+
+      button_profile_1: None,
+      button_profile_2: None,
+      button_profile_3: None,
+      button_profile_4: None,
+
+      mode_profile_1: None,
+      mode_profile_2: None,
+      mode_profile_3: None,
+      mode_profile_4: None,
+
+      uuid_profiles: HashMap::new(),
+      uuid_slots: HashMap::new(),
+
+      battery_passthrough: false
     }
   }
 }
 
-impl Config { 
+impl Config {
   pub fn to_vec(&self) -> Vec<Option<SwitchPad>> {
     return vec!(
       self.switch_pad_1,
@@ -29,4 +119,45 @@ impl Config {
       self.switch_pad_4
     );
   }
+
+  pub fn stick_configs_to_vec(&self) -> Vec<StickConfig> {
+    return vec!(
+      self.stick_config_1,
+      self.stick_config_2,
+      self.stick_config_3,
+      self.stick_config_4
+    );
+  }
+
+  pub fn button_profiles_to_vec(&self) -> Vec<Option<ButtonProfile>> {
+    return vec!(
+      self.button_profile_1.clone(),
+      self.button_profile_2.clone(),
+      self.button_profile_3.clone(),
+      self.button_profile_4.clone()
+    );
+  }
+
+  pub fn mode_profiles_to_vec(&self) -> Vec<Option<ModeProfile>> {
+    return vec!(
+      self.mode_profile_1.clone(),
+      self.mode_profile_2.clone(),
+      self.mode_profile_3.clone(),
+      self.mode_profile_4.clone()
+    );
+  }
+
+  // Looks up a button profile bound to a specific gamepad's (hex-encoded) UUID, if one exists.
+  pub fn get_uuid_profile(&self, uuid: &str) -> Option<&ButtonProfile> {
+    return self.uuid_profiles.get(uuid);
+  }
+
+  // Looks up the slot a specific gamepad's (hex-encoded) UUID remembers being assigned to.
+  pub fn get_uuid_slot(&self, uuid: &str) -> Option<usize> {
+    return self.uuid_slots.get(uuid).copied();
+  }
+
+  pub fn get_battery_passthrough(&self) -> bool {
+    return self.battery_passthrough;
+  }
 }
\ No newline at end of file
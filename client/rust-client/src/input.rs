@@ -3,10 +3,17 @@ use gilrs::{
   EventType,
   GamepadId,
   Axis,
-  Button
+  Button,
+  ff::{BaseEffect, BaseEffectType, EffectBuilder, Effect}
 };
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::time;
+
+use crate::config::StickConfig;
 
 // An enum representing the different Switch controllers that can be emulated.
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum SwitchPad {
   ProController,
   JoyConLSide,
@@ -35,7 +42,7 @@ impl SwitchPad {
 }
 
 // An enum representing all the different buttons on a Switch controller.
-// TODO: What about the home button?
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SwitchButton {
   A,
   B,
@@ -64,13 +71,14 @@ pub enum SwitchButton {
   SLL,
   SRL,
   SLR,
-  SRR
+  SRR,
+  Home,
+  Capture
 }
 
 impl SwitchButton {
   // Returns the bit corresponding to this button.
   pub fn value(&self) -> i32 {
-    // TODO: What about the home button?
     match self {
       Self::A => return 1,
       Self::B => return 1 << 1,
@@ -99,12 +107,31 @@ impl SwitchButton {
       Self::SLL => return 1 << 24,
       Self::SRL => return 1 << 25,
       Self::SLR => return 1 << 26,
-      Self::SRR => return 1 << 27
+      Self::SRR => return 1 << 27,
+      Self::Home => return 1 << 28,
+      Self::Capture => return 1 << 29
+    }
+  }
+
+  /**
+   * Maps a GilRs button to a Switch button depending on the specified pad type.
+   *
+   * If a ButtonProfile is active for this pad, its override is consulted first; only buttons it
+   * doesn't bind fall through to the built-in default mapping below.
+   */
+  pub fn map_button(
+    button: &Button, switch_pad: &SwitchPad, profile: Option<&ButtonProfile>
+  ) -> Result<SwitchButton, String> {
+    if let Some(profile) = profile {
+      if let Some(switch_button) = profile.get(button) {
+        return Ok(*switch_button);
+      }
     }
+    return Self::map_default(button, switch_pad);
   }
 
-  // Maps a GilRs button to a Switch button depending on the specified pad type.
-  pub fn map_button(button: &Button, switch_pad: &SwitchPad) -> Result<SwitchButton, String> {
+  // The built-in GilRs -> Switch button mapping used when no profile override applies.
+  fn map_default(button: &Button, switch_pad: &SwitchPad) -> Result<SwitchButton, String> {
     match button {
       Button::DPadUp => Ok(Self::DU),
       Button::DPadRight => Ok(Self::DR),
@@ -144,6 +171,82 @@ impl SwitchButton {
   }
 }
 
+/**
+ * A struct representing a user-configurable table of GilRs button -> Switch button overrides.
+ *
+ * Profiles are meant to be loaded per slot from Config, and optionally keyed by a gamepad's GilRs
+ * UUID so a given physical pad always gets the same layout regardless of which slot it lands in.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ButtonProfile {
+  bindings: HashMap<Button, SwitchButton>
+}
+
+impl ButtonProfile {
+  pub fn new() -> ButtonProfile {
+    return ButtonProfile {
+      bindings: HashMap::new()
+    }
+  }
+
+  pub fn get(&self, button: &Button) -> Option<&SwitchButton> {
+    return self.bindings.get(button);
+  }
+
+  pub fn bind(&mut self, button: Button, switch_button: SwitchButton) -> () {
+    self.bindings.insert(button, switch_button);
+  }
+}
+
+// A behavior a mapped Switch button can be given on top of just following the physical button.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum ButtonMode {
+  Normal,
+  // Each physical press flips a latched state instead of following the button directly.
+  Toggle,
+  // While held, the emitted bit pulses on/off at the given rate in Hz.
+  Turbo(f32)
+}
+
+// A per-slot table of Switch button -> behavior, consulted every tick to resolve modifiers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ModeProfile {
+  modes: HashMap<SwitchButton, ButtonMode>
+}
+
+impl ModeProfile {
+  pub fn new() -> ModeProfile {
+    return ModeProfile {
+      modes: HashMap::new()
+    }
+  }
+
+  pub fn get(&self, switch_button: &SwitchButton) -> ButtonMode {
+    return *self.modes.get(switch_button).unwrap_or(&ButtonMode::Normal);
+  }
+
+  pub fn set(&mut self, switch_button: SwitchButton, mode: ButtonMode) -> () {
+    self.modes.insert(switch_button, mode);
+  }
+}
+
+// Per-button edge/phase tracking needed to resolve toggle and turbo modifiers each tick.
+struct ModifierState {
+  was_physical: bool,
+  toggled: bool,
+  phase_start: Option<time::Instant>
+}
+
+impl ModifierState {
+  fn new() -> ModifierState {
+    return ModifierState {
+      was_physical: false,
+      toggled: false,
+      phase_start: None
+    }
+  }
+}
+
 /**
  * A struct representing an emulated Switch controller.
  * 
@@ -159,7 +262,32 @@ pub struct EmulatedPad {
   switch_pad: Option<SwitchPad>,
   keyout: i32,
   left: (i32, i32),
-  right: (i32, i32)
+  right: (i32, i32),
+
+  // The rumble effect currently bound to this pad's gamepad, if any. Kept alive here so it isn't
+  // dropped (and thus stopped) between updates.
+  effect: Option<Effect>,
+  rumble: (u16, u16),
+
+  // The last raw (pre-deadzone) normalized axis values, cached since the X and Y components of a
+  // stick arrive as separate events but the deadzone has to be applied radially, to both at once.
+  left_raw: (f32, f32),
+  right_raw: (f32, f32),
+  stick_config: StickConfig,
+
+  // The active button remap override for this pad, if any.
+  profile: Option<ButtonProfile>,
+
+  // The last polled battery level (bits 0-6, 0-100) and charging flag (bit 7), plus when it was
+  // last refreshed so we don't hammer GilRs for power info every tick.
+  power: u8,
+  power_polled_at: Option<time::Instant>,
+
+  // The raw (unmodified) physical press state of each mapped Switch button, and the turbo/toggle
+  // behavior and edge/phase tracking used to turn it into the bit that's actually sent.
+  raw_buttons: HashMap<SwitchButton, bool>,
+  mode_profile: ModeProfile,
+  modifier_states: HashMap<SwitchButton, ModifierState>
 }
 
 impl EmulatedPad {
@@ -170,10 +298,41 @@ impl EmulatedPad {
       switch_pad: None,
       keyout: 0,
       left: (0, 0),
-      right: (0, 0)
+      right: (0, 0),
+
+      effect: None,
+      rumble: (0, 0),
+
+      left_raw: (0.0, 0.0),
+      right_raw: (0.0, 0.0),
+      stick_config: StickConfig::default(),
+
+      profile: None,
+
+      power: 0,
+      power_polled_at: None,
+
+      raw_buttons: HashMap::new(),
+      mode_profile: ModeProfile::new(),
+      modifier_states: HashMap::new()
     }
   }
 
+  // Sets the turbo/toggle behavior table this pad's buttons should be resolved with.
+  pub fn set_mode_profile(&mut self, mode_profile: ModeProfile) -> () {
+    self.mode_profile = mode_profile;
+  }
+
+  // Sets the deadzone/response curve this pad's sticks should be processed with.
+  pub fn set_stick_config(&mut self, stick_config: StickConfig) -> () {
+    self.stick_config = stick_config;
+  }
+
+  // Sets the button remap override this pad should consult before falling back to the defaults.
+  pub fn set_profile(&mut self, profile: Option<ButtonProfile>) -> () {
+    self.profile = profile;
+  }
+
   pub fn get_gamepad_id(&self) -> &Option<GamepadId> {
     return &self.gamepad_id;
   }
@@ -205,6 +364,100 @@ impl EmulatedPad {
   // Actually, this might be better off done in the client rather than here in the emulated pad.
   pub fn soft_disconnect(&mut self) -> () {
     self.switch_pad = None;
+    self.stop_rumble();
+  }
+
+  /**
+   * Drives this pad's gamepad with the given low/high frequency rumble amplitudes (0-65535).
+   *
+   * A persistent effect is built the first time this pad rumbles and reused afterwards, since
+   * GilRs effects need to stay alive for as long as they should keep playing. The gain is updated
+   * in place when the amplitudes change, and the effect is stopped outright once both drop to 0.
+   */
+  pub fn update_rumble(
+    &mut self, gilrs: &mut Gilrs, low_freq_amp: u16, high_freq_amp: u16
+  ) -> () {
+    if self.rumble == (low_freq_amp, high_freq_amp) {
+      return;
+    }
+    self.rumble = (low_freq_amp, high_freq_amp);
+
+    if low_freq_amp == 0 && high_freq_amp == 0 {
+      self.stop_rumble();
+      return;
+    }
+
+    let gamepad_id: GamepadId = match self.gamepad_id {
+      Some(gamepad_id) => gamepad_id,
+      None => return
+    };
+
+    if self.effect.is_none() {
+      self.effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+          kind: BaseEffectType::Strong { magnitude: low_freq_amp },
+          ..Default::default()
+        })
+        .add_effect(BaseEffect {
+          kind: BaseEffectType::Weak { magnitude: high_freq_amp },
+          ..Default::default()
+        })
+        .add_gamepad(gamepad_id)
+        .finish(gilrs)
+        .ok();
+      if let Some(effect) = &self.effect {
+        let _ = effect.play();
+      }
+      return;
+    }
+
+    // Gain is normalized against whichever motor is asked to vibrate hardest.
+    let gain: f32 = (low_freq_amp.max(high_freq_amp) as f32) / (u16::MAX as f32);
+    if let Some(effect) = &self.effect {
+      let _ = effect.set_gain(gain);
+      let _ = effect.play();
+    }
+  }
+
+  // Stops and tears down this pad's rumble effect, if it has one.
+  pub fn stop_rumble(&mut self) -> () {
+    self.rumble = (0, 0);
+    if let Some(effect) = self.effect.take() {
+      let _ = effect.stop();
+    }
+  }
+
+  pub fn get_power(&self) -> u8 {
+    return self.power;
+  }
+
+  /**
+   * Refreshes this pad's battery byte from GilRs' power info, throttled to once every 250ms so we
+   * don't spam GilRs with power queries on every tick.
+   *
+   * The resulting byte packs the battery percentage (0-100) in the low 7 bits and a charging flag
+   * in the high bit, matching the wire format the 0x3277 packet appends per slot.
+   */
+  pub fn update_power(&mut self, gilrs: &mut Gilrs) -> () {
+    let gamepad_id: GamepadId = match self.gamepad_id {
+      Some(gamepad_id) => gamepad_id,
+      None => return
+    };
+    let due: bool = match self.power_polled_at {
+      Some(last) => last.elapsed() >= time::Duration::from_millis(250),
+      None => true
+    };
+    if !due {
+      return;
+    }
+    self.power_polled_at = Some(time::Instant::now());
+
+    self.power = match gilrs.gamepad(gamepad_id).power_info() {
+      gilrs::PowerInfo::Discharging(pct) => pct.min(100),
+      gilrs::PowerInfo::Charging(pct) => pct.min(100) | 0x80,
+      gilrs::PowerInfo::Charged => 100 | 0x80,
+      gilrs::PowerInfo::Wired | gilrs::PowerInfo::Unknown => 0
+    };
   }
 
   // Returns whether this pad is connected by checking if its gamepad id is in GilRs' list of
@@ -226,34 +479,115 @@ impl EmulatedPad {
     }
   }
 
-  // Attempt to update the keyout for a button and its corresponding value.
+  // Attempt to record the physical press state for a button, keyed by its mapped Switch button.
+  // The actual keyout bit is computed later by resolve_modifiers(), since turbo/toggle buttons
+  // need to be re-evaluated every tick rather than just on a raw input change.
   pub fn update_keyout(&mut self, button: &Button, value: &f32) -> () {
     if self.switch_pad.is_some() {
       match &SwitchButton::map_button(
         button,
-        &self.switch_pad.as_ref().unwrap()
+        &self.switch_pad.as_ref().unwrap(),
+        self.profile.as_ref()
       ) {
-        Ok(switch_button) => self.set_del_bit(
-          &switch_button.value(),
-          &(*value as i32)
-        ),
+        Ok(switch_button) => {
+          self.raw_buttons.insert(*switch_button, *value != 0.0);
+        },
         Err(_) => ()
       }
     }
   }
 
+  /**
+   * Resolves every mapped button's raw physical state into the bit that should actually be sent,
+   * applying this pad's turbo/toggle modifiers, and rewrites keyout from scratch.
+   *
+   * Must be called once per tick (from the client's fixed-interval loop) so turbo pulsing and
+   * toggle edge-detection stay timed off elapsed wall-clock time rather than input events, which
+   * don't fire again while a button is just being held down.
+   */
+  pub fn resolve_modifiers(&mut self) -> () {
+    let raw_buttons: Vec<(SwitchButton, bool)> = self.raw_buttons.iter()
+      .map(|(switch_button, physical)| (*switch_button, *physical))
+      .collect();
+
+    for (switch_button, physical) in raw_buttons {
+      let state = self.modifier_states.entry(switch_button).or_insert_with(ModifierState::new);
+      let was_physical: bool = state.was_physical;
+      state.was_physical = physical;
+
+      let bit_on: bool = match self.mode_profile.get(&switch_button) {
+        ButtonMode::Normal => physical,
+        ButtonMode::Toggle => {
+          if physical && !was_physical {
+            state.toggled = !state.toggled;
+          }
+          state.toggled
+        },
+        ButtonMode::Turbo(hz) => {
+          if physical {
+            let phase_start: &time::Instant = state.phase_start.get_or_insert_with(time::Instant::now);
+            let elapsed: f32 = phase_start.elapsed().as_secs_f32();
+            // Each full cycle (on then off) takes 1/hz seconds.
+            ((elapsed * hz * 2.0) as u64) % 2 == 0
+          } else {
+            state.phase_start = None;
+            false
+          }
+        }
+      };
+
+      self.set_del_bit(&switch_button.value(), &(bit_on as i32));
+    }
+  }
+
   // Attempt to update the stick state for an axis and its corresponding value.
   pub fn update_axis(&mut self, axis: &Axis, value: &f32) -> () {
-    let converted: i32 = (*value * 32767.0) as i32;
     match axis {
-      Axis::LeftStickX => self.left.0 = converted,
-      Axis::LeftStickY => self.left.1 = converted,
-      Axis::RightStickX => self.right.0 = converted,
-      Axis::RightStickY => self.right.1 = converted,
+      Axis::LeftStickX => {
+        self.left_raw.0 = *value;
+        self.left = Self::apply_deadzone(self.left_raw, &self.stick_config);
+      },
+      Axis::LeftStickY => {
+        self.left_raw.1 = *value;
+        self.left = Self::apply_deadzone(self.left_raw, &self.stick_config);
+      },
+      Axis::RightStickX => {
+        self.right_raw.0 = *value;
+        self.right = Self::apply_deadzone(self.right_raw, &self.stick_config);
+      },
+      Axis::RightStickY => {
+        self.right_raw.1 = *value;
+        self.right = Self::apply_deadzone(self.right_raw, &self.stick_config);
+      },
       _ => ()
     }
   }
 
+  /**
+   * Applies a radial deadzone and response curve to a raw (x, y) stick pair, returning the result
+   * scaled to the i32 range the Switch protocol expects.
+   *
+   * Scaling the two axes together (rather than independently) keeps diagonal deflection from
+   * being distorted into a square, which a naive per-axis deadzone would do.
+   */
+  fn apply_deadzone(raw: (f32, f32), stick_config: &StickConfig) -> (i32, i32) {
+    let (x, y): (f32, f32) = raw;
+    let mag: f32 = (x * x + y * y).sqrt();
+    if mag < stick_config.get_inner_dz() {
+      return (0, 0);
+    }
+
+    let scaled: f32 = ((mag - stick_config.get_inner_dz())
+      / (stick_config.get_outer_dz() - stick_config.get_inner_dz()))
+      .clamp(0.0, 1.0)
+      .powf(stick_config.get_curve());
+
+    return (
+      ((x / mag) * scaled * 32767.0) as i32,
+      ((y / mag) * scaled * 32767.0) as i32
+    );
+  }
+
   // Updates the keyout using a bitwise OR if an input value isn't 0, otherwise a bitwise AND using
   // the complement.
   pub fn set_del_bit(&mut self, bit: &i32, value: &i32) -> () {
@@ -263,4 +597,34 @@ impl EmulatedPad {
       self.keyout = self.keyout & !bit;
     }
   }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // StickConfig's fields are private to the config module, so these exercise apply_deadzone
+  // against its default (inner_dz: 0.1, outer_dz: 1.0, curve: 1.0).
+  #[test]
+  fn apply_deadzone_at_exactly_the_inner_deadzone_is_neutral() {
+    let stick_config = StickConfig::default();
+    assert_eq!(EmulatedPad::apply_deadzone((stick_config.get_inner_dz(), 0.0), &stick_config), (0, 0));
+  }
+
+  #[test]
+  fn apply_deadzone_at_full_deflection_maxes_out() {
+    let stick_config = StickConfig::default();
+    assert_eq!(EmulatedPad::apply_deadzone((1.0, 0.0), &stick_config), (32767, 0));
+    assert_eq!(EmulatedPad::apply_deadzone((-1.0, 0.0), &stick_config), (-32767, 0));
+  }
+
+  #[test]
+  fn apply_deadzone_scales_diagonal_input_radially() {
+    let stick_config = StickConfig::default();
+    let (x, y) = EmulatedPad::apply_deadzone((0.5, 0.5), &stick_config);
+    // Radial scaling (rather than per-axis) keeps a diagonal's two components equal to each other,
+    // and clamped to the Switch's i32 range rather than distorted out past full deflection.
+    assert_eq!(x, y);
+    assert!(x > 0 && x <= 32767);
+  }
 }
\ No newline at end of file
@@ -1,7 +1,9 @@
 pub mod client;
+pub mod config;
 pub mod input;
 
 use crate::client::Client;
+use crate::config::Config;
 
 use clap::{Arg, App, ArgMatches};
 
@@ -23,6 +25,8 @@ fn main() {
     )
     .get_matches();
   let ip: &str = matches.value_of("ip").unwrap();
-  let mut client: Client = Client::new();
+  let config: Config = confy::load_path("./config.toml")
+    .expect("Expected a config to be generated from a file.");
+  let mut client: Client = Client::new(config);
   client.start(ip, true);
 }